@@ -50,18 +50,33 @@ pub struct CrateRelease {
     pub yanked: bool
 }
 
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum GitReference {
+    Tag(String),
+    Branch(String),
+    Rev(String)
+}
+
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub enum CrateDep {
-    External(VersionReq),
+    External {
+        req: VersionReq,
+        features: Vec<String>,
+        default_features: bool,
+        optional: bool,
+        package: Option<CrateName>
+    },
+    Registry { name: String, req: VersionReq, package: Option<CrateName> },
+    Git { url: String, reference: Option<GitReference> },
+    Inherited { features_added: Vec<CrateName> },
     Internal(RelativePathBuf)
 }
 
 impl CrateDep {
     pub fn is_external(&self) -> bool {
-        if let &CrateDep::External(_) = self {
-            true
-        } else {
-            false
+        match *self {
+            CrateDep::External { .. } | CrateDep::Registry { .. } | CrateDep::Inherited { .. } => true,
+            CrateDep::Git { .. } | CrateDep::Internal(_) => false
         }
     }
 }
@@ -73,11 +88,35 @@ pub struct CrateDeps {
     pub build: OrderMap<CrateName, CrateDep>
 }
 
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum UpdateSeverity {
+    None,
+    Patch,
+    Minor,
+    Major
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum AnalysisSource {
+    CratesIo,
+    Registry(String),
+    NotChecked
+}
+
 #[derive(Debug)]
 pub struct AnalyzedDependency {
     pub required: VersionReq,
     pub latest_that_matches: Option<Version>,
-    pub latest: Option<Version>
+    pub latest: Option<Version>,
+    pub source: AnalysisSource,
+    pub features: Vec<String>,
+    pub default_features: bool,
+    pub optional: bool,
+    pub matched_version_yanked: bool,
+    pub latest_non_yanked_that_matches: Option<Version>,
+    /// The crate name to query for freshness, when it differs from the dependency's
+    /// local key in the manifest (a renamed dependency via `package = "..."`).
+    pub package: Option<CrateName>
 }
 
 impl AnalyzedDependency {
@@ -85,13 +124,120 @@ impl AnalyzedDependency {
         AnalyzedDependency {
             required,
             latest_that_matches: None,
-            latest: None
+            latest: None,
+            source: AnalysisSource::CratesIo,
+            features: Vec::new(),
+            default_features: true,
+            optional: false,
+            matched_version_yanked: false,
+            latest_non_yanked_that_matches: None,
+            package: None
+        }
+    }
+
+    pub fn new_external(
+        required: VersionReq,
+        features: Vec<String>,
+        default_features: bool,
+        optional: bool,
+        package: Option<CrateName>
+    ) -> AnalyzedDependency {
+        AnalyzedDependency {
+            features,
+            default_features,
+            optional,
+            package,
+            ..AnalyzedDependency::new(required)
+        }
+    }
+
+    pub fn new_in_registry(
+        required: VersionReq, registry: String, package: Option<CrateName>
+    ) -> AnalyzedDependency {
+        AnalyzedDependency {
+            source: AnalysisSource::Registry(registry),
+            package,
+            ..AnalyzedDependency::new(required)
+        }
+    }
+
+    pub fn new_not_checked() -> AnalyzedDependency {
+        AnalyzedDependency {
+            source: AnalysisSource::NotChecked,
+            ..AnalyzedDependency::new(VersionReq::any())
         }
     }
 
     pub fn is_outdated(&self) -> bool {
         self.latest > self.latest_that_matches
     }
+
+    /// The crate name to look up in the registry for this dependency: the renamed
+    /// `package`, if any, or otherwise its local key in the manifest.
+    pub fn package_name<'a>(&'a self, local_name: &'a CrateName) -> &'a CrateName {
+        self.package.as_ref().unwrap_or(local_name)
+    }
+
+    pub fn is_yanked(&self) -> bool {
+        self.matched_version_yanked
+    }
+
+    pub fn severity(&self) -> UpdateSeverity {
+        let matching = match self.latest_that_matches {
+            Some(ref version) => version,
+            None => return UpdateSeverity::None
+        };
+        let latest = match self.latest {
+            Some(ref version) => version,
+            None => return UpdateSeverity::None
+        };
+
+        if latest <= matching {
+            return UpdateSeverity::None;
+        }
+
+        if latest.major != matching.major {
+            UpdateSeverity::Major
+        } else if latest.major == 0 {
+            if latest.minor != matching.minor {
+                UpdateSeverity::Major
+            } else if latest.patch != matching.patch {
+                UpdateSeverity::Patch
+            } else {
+                UpdateSeverity::None
+            }
+        } else if latest.minor != matching.minor {
+            UpdateSeverity::Minor
+        } else if latest.patch != matching.patch {
+            UpdateSeverity::Patch
+        } else {
+            UpdateSeverity::None
+        }
+    }
+
+    pub fn is_breaking(&self) -> bool {
+        self.severity() == UpdateSeverity::Major
+    }
+}
+
+fn analyze_dep(
+    name: &CrateName, dep: &CrateDep, workspace_deps: Option<&CrateDeps>
+) -> Option<AnalyzedDependency> {
+    match *dep {
+        CrateDep::External { ref req, ref features, default_features, optional, ref package } =>
+            Some(AnalyzedDependency::new_external(
+                req.clone(), features.clone(), default_features, optional, package.clone()
+            )),
+        CrateDep::Registry { name: ref registry, ref req, ref package } =>
+            Some(AnalyzedDependency::new_in_registry(req.clone(), registry.clone(), package.clone())),
+        CrateDep::Git { .. } =>
+            Some(AnalyzedDependency::new_not_checked()),
+        CrateDep::Inherited { .. } => {
+            let workspace_dep = workspace_deps?.main.get(name)?;
+            analyze_dep(name, workspace_dep, None)
+        },
+        CrateDep::Internal(_) => None
+    }
 }
 
 #[derive(Debug)]
@@ -102,27 +248,15 @@ pub struct AnalyzedDependencies {
 }
 
 impl AnalyzedDependencies {
-    pub fn new(deps: &CrateDeps) -> AnalyzedDependencies {
+    pub fn new(deps: &CrateDeps, workspace_deps: Option<&CrateDeps>) -> AnalyzedDependencies {
         let main = deps.main.iter().filter_map(|(name, dep)| {
-            if let &CrateDep::External(ref req) = dep {
-                Some((name.clone(), AnalyzedDependency::new(req.clone())))
-            } else {
-                None
-            }
+            analyze_dep(name, dep, workspace_deps).map(|analyzed| (name.clone(), analyzed))
         }).collect();
         let dev = deps.dev.iter().filter_map(|(name, dep)| {
-            if let &CrateDep::External(ref req) = dep {
-                Some((name.clone(), AnalyzedDependency::new(req.clone())))
-            } else {
-                None
-            }
+            analyze_dep(name, dep, workspace_deps).map(|analyzed| (name.clone(), analyzed))
         }).collect();
         let build = deps.build.iter().filter_map(|(name, dep)| {
-            if let &CrateDep::External(ref req) = dep {
-                Some((name.clone(), AnalyzedDependency::new(req.clone())))
-            } else {
-                None
-            }
+            analyze_dep(name, dep, workspace_deps).map(|analyzed| (name.clone(), analyzed))
         }).collect();
         AnalyzedDependencies { main, dev, build }
     }
@@ -136,11 +270,36 @@ impl AnalyzedDependencies {
             .any(|(_, dep)| dep.is_outdated());
         main_any_outdated || dev_any_outdated || build_any_outdated
     }
+
+    pub fn any_breaking(&self) -> bool {
+        let main_any_breaking = self.main.iter()
+            .any(|(_, dep)| dep.is_breaking());
+        let dev_any_breaking = self.dev.iter()
+            .any(|(_, dep)| dep.is_breaking());
+        let build_any_breaking = self.build.iter()
+            .any(|(_, dep)| dep.is_breaking());
+        main_any_breaking || dev_any_breaking || build_any_breaking
+    }
+
+    pub fn any_yanked(&self) -> bool {
+        let main_any_yanked = self.main.iter()
+            .any(|(_, dep)| dep.is_yanked());
+        let dev_any_yanked = self.dev.iter()
+            .any(|(_, dep)| dep.is_yanked());
+        let build_any_yanked = self.build.iter()
+            .any(|(_, dep)| dep.is_yanked());
+        main_any_yanked || dev_any_yanked || build_any_yanked
+    }
 }
 
 #[derive(Clone, Debug)]
 pub enum CrateManifest {
     Package(CrateName, CrateDeps),
-    Workspace { members: Vec<RelativePathBuf> },
-    Mixed { name: CrateName, deps: CrateDeps, members: Vec<RelativePathBuf> }
+    Workspace { members: Vec<RelativePathBuf>, workspace_deps: CrateDeps },
+    Mixed {
+        name: CrateName,
+        deps: CrateDeps,
+        members: Vec<RelativePathBuf>,
+        workspace_deps: CrateDeps
+    }
 }
@@ -0,0 +1,2 @@
+pub mod crates;
+pub mod resolution;
@@ -0,0 +1,323 @@
+use std::collections::{HashMap, HashSet};
+
+use ordermap::OrderMap;
+use semver::{Version, VersionReq};
+
+use super::crates::CrateName;
+
+/// Every version of a package present in the index, together with the
+/// dependency requirements each of those versions declares. This is the
+/// transitive information a `Resolver` needs to tell whether upgrading a
+/// package conflicts with another one also being upgraded.
+#[derive(Clone, Debug)]
+pub struct PackageVersions {
+    pub name: CrateName,
+    pub versions: Vec<Version>,
+    pub requirements: HashMap<Version, Vec<(CrateName, VersionReq)>>
+}
+
+/// A set of packages whose decided versions cannot all hold at once, along
+/// with a human-readable explanation of why. The set names the package that
+/// could not be given a satisfying version and the decided packages whose
+/// requirements on it were in force; it is not reduced to a smallest subset,
+/// so it can include requirers whose own requirement was met.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Conflict {
+    pub packages: Vec<CrateName>,
+    pub reason: String
+}
+
+/// The outcome of trying to resolve a set of "upgrade to latest" targets.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Resolution {
+    /// Every target package can be upgraded to its latest version at once.
+    Satisfiable,
+    /// Upgrading everything to latest is impossible; lists the conflicting
+    /// sets of packages found while trying.
+    Conflicting(Vec<Conflict>)
+}
+
+/// A small PubGrub-style unit-propagation/backtracking solver: given a set of
+/// packages we'd like to bump to their latest version plus the transitive
+/// requirements fetched from the index, it determines whether that's
+/// mutually satisfiable. Target packages are pinned to the version we
+/// actually want ("latest everywhere"); any package they transitively
+/// require is pulled in and decided against every requirement known about it
+/// so far. Because transitive packages are discovered one requirer at a
+/// time, a decision made early can later turn out to conflict with a
+/// requirement that only shows up once another requirer is processed — when
+/// that happens the earlier decision is undone, the version that didn't work
+/// is excluded, and the package is re-decided against the fuller constraint
+/// set, so order of discovery doesn't produce false conflicts. Only a
+/// genuine conflict with a *target* (which can't be moved off "latest") is
+/// reported as unresolvable.
+pub struct Resolver<'a> {
+    index: &'a HashMap<CrateName, PackageVersions>
+}
+
+impl<'a> Resolver<'a> {
+    pub fn new(index: &'a HashMap<CrateName, PackageVersions>) -> Resolver<'a> {
+        Resolver { index }
+    }
+
+    pub fn resolve(&self, targets: &[(CrateName, Version)]) -> Resolution {
+        let desired: HashMap<CrateName, Version> = targets.iter().cloned().collect();
+        let mut decided: OrderMap<CrateName, Version> = OrderMap::new();
+        let mut excluded: HashMap<CrateName, HashSet<Version>> = HashMap::new();
+        let mut remaining: Vec<CrateName> = targets.iter().map(|(name, _)| name.clone()).collect();
+        let mut conflicts: Vec<Conflict> = Vec::new();
+
+        while let Some(name) = remaining.pop() {
+            if decided.contains_key(&name) {
+                continue;
+            }
+
+            let constraints = self.constraints_on(&name, &decided);
+
+            let version = match desired.get(&name) {
+                // A target is pinned to the version we actually want to
+                // upgrade to; it's never downgraded to paper over a
+                // conflict, so a failure to satisfy `constraints` below is
+                // reported rather than silently avoided.
+                Some(desired_version) => Some(desired_version.clone()),
+                None => self.best_candidate(&name, &constraints, excluded.get(&name))
+            };
+
+            let version = match version {
+                Some(version) => version,
+                None => {
+                    let mut packages: Vec<CrateName> =
+                        constraints.iter().map(|(requirer, _)| requirer.clone()).collect();
+                    packages.push(name.clone());
+                    conflicts.push(Conflict {
+                        packages,
+                        reason: format!(
+                            "no version of {} satisfies every requirement already selected",
+                            name.as_ref()
+                        )
+                    });
+                    continue;
+                }
+            };
+
+            for (requirer, req) in &constraints {
+                if !req.matches(&version) {
+                    conflicts.push(Conflict {
+                        packages: vec![requirer.clone(), name.clone()],
+                        reason: format!(
+                            "{} requires {} {}, but {} {} was selected",
+                            requirer.as_ref(), name.as_ref(), req, name.as_ref(), version
+                        )
+                    });
+                }
+            }
+
+            decided.insert(name.clone(), version.clone());
+
+            // `name` may itself require packages that were already decided
+            // before this requirement was known (or not yet decided at
+            // all); check the former now and enqueue the latter.
+            if let Some(requirements) = self.index.get(&name)
+                .and_then(|package| package.requirements.get(&version))
+            {
+                for (dep_name, dep_req) in requirements {
+                    match decided.get(dep_name) {
+                        Some(dep_version) if !dep_req.matches(dep_version) => {
+                            if desired.contains_key(dep_name) {
+                                // `dep_name` is itself a target pinned to
+                                // "latest everywhere"; there's no other
+                                // version to backtrack to, so this is a
+                                // genuine, unresolvable conflict. Name every
+                                // other requirer of `dep_name` too, so the
+                                // reported set actually explains the
+                                // conflict rather than just the requirement
+                                // that tripped it.
+                                let mut packages = vec![name.clone(), dep_name.clone()];
+                                for (other_requirer, _) in self.constraints_on(dep_name, &decided) {
+                                    if !packages.contains(&other_requirer) {
+                                        packages.push(other_requirer);
+                                    }
+                                }
+                                conflicts.push(Conflict {
+                                    packages,
+                                    reason: format!(
+                                        "{} {} requires {} {}, but {} {} was selected",
+                                        name.as_ref(), version, dep_name.as_ref(), dep_req,
+                                        dep_name.as_ref(), dep_version
+                                    )
+                                });
+                            } else {
+                                // `dep_name` was decided from a narrower
+                                // constraint set than we now know about:
+                                // backtrack by undoing that decision, ruling
+                                // out the version that didn't work, and
+                                // re-deciding it against every requirement
+                                // now in scope.
+                                let rejected = decided.remove(dep_name)
+                                    .expect("dep_name was just found via decided.get");
+                                excluded.entry(dep_name.clone()).or_insert_with(HashSet::new)
+                                    .insert(rejected);
+                                remaining.push(dep_name.clone());
+                            }
+                        },
+                        Some(_) => {},
+                        None => remaining.push(dep_name.clone())
+                    }
+                }
+            }
+        }
+
+        let mut seen_conflicts: HashSet<Vec<CrateName>> = HashSet::new();
+        conflicts.retain(|conflict| {
+            let mut key = conflict.packages.clone();
+            key.sort();
+            seen_conflicts.insert(key)
+        });
+
+        if conflicts.is_empty() {
+            Resolution::Satisfiable
+        } else {
+            Resolution::Conflicting(conflicts)
+        }
+    }
+
+    /// Every requirement already-decided packages place on `name`.
+    fn constraints_on(
+        &self, name: &CrateName, decided: &OrderMap<CrateName, Version>
+    ) -> Vec<(CrateName, VersionReq)> {
+        decided.iter().filter_map(|(decided_name, decided_version)| {
+            let requirements = self.index.get(decided_name)?.requirements.get(decided_version)?;
+            requirements.iter()
+                .find(|(dep_name, _)| dep_name == name)
+                .map(|(_, req)| (decided_name.clone(), req.clone()))
+        }).collect()
+    }
+
+    /// The newest version of `name` in the index that satisfies every one of
+    /// `constraints` and isn't in `excluded` (versions already tried and
+    /// backtracked out of because they turned out to conflict).
+    fn best_candidate(
+        &self,
+        name: &CrateName,
+        constraints: &[(CrateName, VersionReq)],
+        excluded: Option<&HashSet<Version>>
+    ) -> Option<Version> {
+        let package = self.index.get(name)?;
+
+        package.versions.iter()
+            .filter(|version| excluded.map_or(true, |excluded| !excluded.contains(version)))
+            .filter(|version| constraints.iter().all(|(_, req)| req.matches(version)))
+            .max()
+            .cloned()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn name(input: &str) -> CrateName {
+        input.parse().unwrap()
+    }
+
+    fn version(input: &str) -> Version {
+        Version::parse(input).unwrap()
+    }
+
+    fn req(input: &str) -> VersionReq {
+        VersionReq::parse(input).unwrap()
+    }
+
+    fn package(
+        crate_name: &str, versions: &[&str], requirements: Vec<(&str, Vec<(&str, &str)>)>
+    ) -> PackageVersions {
+        let requirements = requirements.into_iter().map(|(at_version, deps)| {
+            let deps = deps.into_iter()
+                .map(|(dep_name, dep_req)| (name(dep_name), req(dep_req)))
+                .collect();
+            (version(at_version), deps)
+        }).collect();
+
+        PackageVersions {
+            name: name(crate_name),
+            versions: versions.iter().map(|v| version(v)).collect(),
+            requirements
+        }
+    }
+
+    #[test]
+    fn independent_targets_are_satisfiable() {
+        let mut index = HashMap::new();
+        index.insert(name("a"), package("a", &["2.0.0"], vec![]));
+        index.insert(name("b"), package("b", &["2.0.0"], vec![]));
+
+        let targets = vec![(name("a"), version("2.0.0")), (name("b"), version("2.0.0"))];
+
+        assert_eq!(Resolver::new(&index).resolve(&targets), Resolution::Satisfiable);
+    }
+
+    #[test]
+    fn conflicting_requirements_on_a_shared_transitive_crate_are_detected() {
+        let mut index = HashMap::new();
+        index.insert(
+            name("a"),
+            package("a", &["2.0.0"], vec![("2.0.0", vec![("shared", "=1.0.0")])])
+        );
+        index.insert(
+            name("b"),
+            package("b", &["2.0.0"], vec![("2.0.0", vec![("shared", "=2.0.0")])])
+        );
+        index.insert(name("shared"), package("shared", &["1.0.0", "2.0.0"], vec![]));
+
+        let targets = vec![(name("a"), version("2.0.0")), (name("b"), version("2.0.0"))];
+
+        match Resolver::new(&index).resolve(&targets) {
+            Resolution::Conflicting(conflicts) => {
+                assert!(!conflicts.is_empty());
+                let involved: Vec<CrateName> =
+                    conflicts.into_iter().flat_map(|c| c.packages).collect();
+                assert!(involved.contains(&name("a")));
+                assert!(involved.contains(&name("b")));
+                assert!(involved.contains(&name("shared")));
+            },
+            Resolution::Satisfiable => panic!("expected a conflict through the shared crate")
+        }
+    }
+
+    #[test]
+    fn transitive_candidate_search_prefers_the_newest_satisfying_version() {
+        let mut index = HashMap::new();
+        index.insert(
+            name("a"),
+            package("a", &["1.0.0"], vec![("1.0.0", vec![("shared", "^1.0.0")])])
+        );
+        index.insert(name("shared"), package("shared", &["1.5.0", "2.0.0"], vec![]));
+
+        let targets = vec![(name("a"), version("1.0.0"))];
+
+        assert_eq!(Resolver::new(&index).resolve(&targets), Resolution::Satisfiable);
+    }
+
+    #[test]
+    fn backtracks_a_transitive_decision_made_before_a_later_constraint_was_known() {
+        // `b` is processed first and pins `shared` to its newest version
+        // that satisfies `^1.0.0` alone (1.9.0). Only once `a` is decided
+        // does the narrower `=1.5.0` constraint show up; a solver that
+        // doesn't revisit the earlier decision would wrongly report this as
+        // conflicting, even though 1.5.0 satisfies both requirers.
+        let mut index = HashMap::new();
+        index.insert(
+            name("a"),
+            package("a", &["1.0.0"], vec![("1.0.0", vec![("shared", "=1.5.0")])])
+        );
+        index.insert(
+            name("b"),
+            package("b", &["1.0.0"], vec![("1.0.0", vec![("shared", "^1.0.0")])])
+        );
+        index.insert(name("shared"), package("shared", &["1.5.0", "1.9.0"], vec![]));
+
+        let targets = vec![(name("a"), version("1.0.0")), (name("b"), version("1.0.0"))];
+
+        assert_eq!(Resolver::new(&index).resolve(&targets), Resolution::Satisfiable);
+    }
+}